@@ -1,8 +1,9 @@
 // This file is a binding for the candle-core and candle-transformers libraries.
 // It is based on https://github.com/huggingface/candle/tree/main/candle-examples/examples/bert
+use std::collections::HashMap;
 use std::ffi::{c_char, CStr, CString};
 use std::sync::Arc;
-use std::sync::Mutex;
+use std::sync::RwLock;
 
 use anyhow::{Error as E, Result};
 use candle_core::{DType, Device, Tensor};
@@ -13,16 +14,92 @@ use tokenizers::Tokenizer;
 use tokenizers::TruncationParams;
 use tokenizers::TruncationStrategy;
 use tokenizers::TruncationDirection;
+use tokenizers::PaddingParams;
+use tokenizers::PaddingStrategy;
+
+// Where to load model weights from
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum WeightSource {
+    Safetensors,
+    Pytorch,
+}
+
+// How to pool token embeddings into a single sentence embedding
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PoolingMode {
+    Mean,
+    Cls,
+}
+
+// Configuration for loading a BertSimilarity model
+pub struct EmbedderOptions {
+    pub model_id: String,
+    pub revision: Option<String>,
+    pub weight_source: Option<WeightSource>,
+    pub adapter_id: Option<String>,
+    pub use_cpu: bool,
+    pub normalize: bool,
+    pub pooling: PoolingMode,
+}
+
+impl Default for EmbedderOptions {
+    fn default() -> Self {
+        Self {
+            model_id: String::new(),
+            revision: None,
+            weight_source: None,
+            adapter_id: None,
+            use_cpu: false,
+            normalize: true,
+            pooling: PoolingMode::Mean,
+        }
+    }
+}
+
+// A trained sentence-transformers Dense projection layer applied after pooling
+struct DenseLayer {
+    linear: candle_nn::Linear,
+    use_tanh: bool,
+}
+
+// Config for a sentence-transformers `2_Dense` module (see sbert's Dense.py)
+#[derive(serde::Deserialize)]
+struct DenseConfig {
+    in_features: usize,
+    out_features: usize,
+    #[serde(default)]
+    activation_function: Option<String>,
+}
 
 // Structure to hold BERT model and tokenizer for semantic similarity
 pub struct BertSimilarity {
     model: BertModel,
     tokenizer: Tokenizer,
     device: Device,
+    pooling: PoolingMode,
+    normalize: bool,
+    dense: Option<DenseLayer>,
 }
 
+// Handle under which the legacy (single-model) FFI functions store their model
+const DEFAULT_MODEL_HANDLE: &str = "default";
+
 lazy_static::lazy_static! {
-    static ref BERT_SIMILARITY: Arc<Mutex<Option<BertSimilarity>>> = Arc::new(Mutex::new(None));
+    // Registry of named model instances, so a process can serve more than one model at once
+    // (e.g. a fast model for one traffic class and a larger one for another).
+    static ref MODEL_REGISTRY: Arc<RwLock<HashMap<String, BertSimilarity>>> = Arc::new(RwLock::new(HashMap::new()));
+}
+
+// Register a loaded model under `handle`, replacing any previous model with that handle
+fn register_model(handle: &str, model: BertSimilarity) {
+    let mut registry = MODEL_REGISTRY.write().unwrap();
+    registry.insert(handle.to_string(), model);
+}
+
+// Run `f` against the model registered under `handle`, if any
+fn with_model<T>(handle: &str, f: impl FnOnce(&BertSimilarity) -> T) -> Option<T> {
+    let registry = MODEL_REGISTRY.read().unwrap();
+    registry.get(handle).map(f)
 }
 
 // Structure to hold tokenization result
@@ -36,24 +113,33 @@ pub struct TokenizationResult {
 
 impl BertSimilarity {
     pub fn new(model_id: &str, use_cpu: bool) -> Result<Self> {
-        let device = if use_cpu {
+        Self::with_options(EmbedderOptions {
+            model_id: model_id.to_string(),
+            use_cpu,
+            ..Default::default()
+        })
+    }
+
+    pub fn with_options(options: EmbedderOptions) -> Result<Self> {
+        let device = if options.use_cpu {
             Device::Cpu
         } else {
             Device::cuda_if_available(0)?
         };
 
         // Default to a sentence transformer model if not specified or empty
-        let model_id = if model_id.is_empty() {
+        let model_id = if options.model_id.is_empty() {
             "sentence-transformers/all-MiniLM-L6-v2"
         } else {
-            model_id
+            options.model_id.as_str()
         };
 
-        // Load model and tokenizer from HF
+        // Load model and tokenizer from HF, pinned to the requested revision (defaults to main)
+        let revision = options.revision.unwrap_or_else(|| "main".to_string());
         let repo = Repo::with_revision(
-            model_id.to_string(), 
-            RepoType::Model, 
-            "main".to_string()  // Use main branch instead of PR/21
+            model_id.to_string(),
+            RepoType::Model,
+            revision.clone()
         );
 
         let (config_filename, tokenizer_filename, weights_filename, use_pth) = {
@@ -62,19 +148,22 @@ impl BertSimilarity {
             let config = api.get("config.json")?;
             let tokenizer = api.get("tokenizer.json")?;
 
-            // Try to get safetensors first, if that fails, fall back to pytorch_model.bin. This is for BAAI models
-            // create a special case for BAAI to download the correct weights to avoid downloading the wrong weights
-            let (weights, use_pth) = if model_id.starts_with("BAAI/") {
-                // BAAI models typically use PyTorch model format
-                (api.get("pytorch_model.bin")?, true)
-            } else {
-                match api.get("model.safetensors") {
+            // Honor an explicit weight source; otherwise fall back to the BAAI heuristic,
+            // then to safetensors-with-pytorch-fallback.
+            let (weights, use_pth) = match options.weight_source {
+                Some(WeightSource::Pytorch) => (api.get("pytorch_model.bin")?, true),
+                Some(WeightSource::Safetensors) => (api.get("model.safetensors")?, false),
+                None if model_id.starts_with("BAAI/") => {
+                    // BAAI models typically use PyTorch model format
+                    (api.get("pytorch_model.bin")?, true)
+                }
+                None => match api.get("model.safetensors") {
                     Ok(weights) => (weights, false),
                     Err(_) => {
                         println!("Safetensors model not found, trying PyTorch model instead...");
                         (api.get("pytorch_model.bin")?, true)
                     }
-                }
+                },
             };
 
             (config, tokenizer, weights, use_pth)
@@ -87,21 +176,85 @@ impl BertSimilarity {
         // Use the approximate GELU for better performance
         config.hidden_act = HiddenAct::GeluApproximate;
 
-        let vb = if use_pth {
-            VarBuilder::from_pth(&weights_filename, DTYPE, &device)?
-        } else {
-            unsafe { VarBuilder::from_mmaped_safetensors(&[weights_filename], DTYPE, &device)? }
+        // Without a LoRA adapter, load weights the usual (mmap-able) way. With one, the base
+        // weights need to be materialized so the adapter's A/B deltas can be merged into them
+        // before BertModel ever sees them - BertModel doesn't expose its internal linears for
+        // post-hoc patching, so merging into the weight tensors is how the adapter gets applied.
+        let vb = match &options.adapter_id {
+            Some(adapter_id) => {
+                let mut weights = load_weights_map(&weights_filename, use_pth, &device)?;
+                merge_lora_adapter(&mut weights, adapter_id, &device)?;
+                VarBuilder::from_tensors(weights, DTYPE, &device)
+            }
+            None if use_pth => VarBuilder::from_pth(&weights_filename, DTYPE, &device)?,
+            None => unsafe { VarBuilder::from_mmaped_safetensors(&[weights_filename], DTYPE, &device)? },
         };
 
         let model = BertModel::load(vb, &config)?;
 
+        // Many sentence-transformers checkpoints ship a trained Dense projection after pooling
+        // (`2_Dense/`); load it if present, otherwise fall back to the pooled embedding as-is.
+        let dense = {
+            let api = Api::new()?;
+            let dense_repo = Repo::with_revision(model_id.to_string(), RepoType::Model, revision);
+            let api = api.repo(dense_repo);
+
+            match api.get("2_Dense/config.json") {
+                Ok(dense_config_filename) => {
+                    let dense_config = std::fs::read_to_string(dense_config_filename)?;
+                    let dense_config: DenseConfig = serde_json::from_str(&dense_config)?;
+
+                    let (dense_weights_filename, dense_use_pth) = match api.get("2_Dense/model.safetensors") {
+                        Ok(weights) => (weights, false),
+                        Err(_) => (api.get("2_Dense/pytorch_model.bin")?, true),
+                    };
+
+                    let dense_vb = if dense_use_pth {
+                        VarBuilder::from_pth(&dense_weights_filename, DTYPE, &device)?
+                    } else {
+                        unsafe { VarBuilder::from_mmaped_safetensors(&[dense_weights_filename], DTYPE, &device)? }
+                    };
+
+                    // The Dense module keeps its weights under `linear.` (`linear.weight`, `linear.bias`)
+                    let linear = candle_nn::linear(dense_config.in_features, dense_config.out_features, dense_vb.pp("linear"))?;
+
+                    Some(DenseLayer {
+                        linear,
+                        use_tanh: dense_config.activation_function
+                            .as_deref()
+                            .map(|activation| activation.contains("Tanh"))
+                            .unwrap_or(false),
+                    })
+                }
+                Err(_) => None,
+            }
+        };
+
         Ok(Self {
             model,
             tokenizer,
             device,
+            pooling: options.pooling,
+            normalize: options.normalize,
+            dense,
         })
     }
 
+    // Apply the sentence-transformers Dense projection, if one was loaded
+    fn apply_dense(&self, embedding: &Tensor) -> Result<Tensor> {
+        match &self.dense {
+            Some(dense) => {
+                let projected = candle_nn::Module::forward(&dense.linear, embedding)?;
+                if dense.use_tanh {
+                    Ok(projected.tanh()?)
+                } else {
+                    Ok(projected)
+                }
+            }
+            None => Ok(embedding.clone()),
+        }
+    }
+
     // Tokenize a text string
     pub fn tokenize_text(&self, text: &str, max_length: Option<usize>) -> Result<(Vec<i32>, Vec<String>)> {
         // Encode the text with the tokenizer
@@ -149,15 +302,91 @@ impl BertSimilarity {
         // Run the text through BERT with attention mask
         let embeddings = self.model.forward(&token_ids_tensor, &token_type_ids, Some(&attention_mask_tensor))?;
         
-        // Mean pooling: sum over tokens and divide by attention mask sum
-        let sum_embeddings = embeddings.sum(1)?;
-        let attention_sum = attention_mask_tensor.sum(1)?.to_dtype(embeddings.dtype())?;
-        let pooled = sum_embeddings.broadcast_div(&attention_sum)?;
-        
-        // Convert to float32 and normalize
+        let pooled = match self.pooling {
+            PoolingMode::Mean => {
+                // Mean pooling: sum over tokens and divide by attention mask sum
+                let sum_embeddings = embeddings.sum(1)?;
+                let attention_sum = attention_mask_tensor.sum(1)?.to_dtype(embeddings.dtype())?;
+                sum_embeddings.broadcast_div(&attention_sum)?
+            }
+            PoolingMode::Cls => {
+                // CLS pooling: take the first token's embedding
+                embeddings.narrow(1, 0, 1)?.squeeze(1)?
+            }
+        };
+
+        // Convert to float32, apply the Dense projection (if any), and normalize
         let embedding = pooled.to_dtype(DType::F32)?;
-        
-        normalize_l2(&embedding)
+        let embedding = self.apply_dense(&embedding)?;
+
+        if self.normalize {
+            normalize_l2(&embedding)
+        } else {
+            Ok(embedding)
+        }
+    }
+
+    // Get embeddings for a batch of texts in a single forward pass
+    pub fn get_embeddings_batch(&self, texts: &[&str], max_length: Option<usize>) -> Result<Tensor> {
+        if texts.is_empty() {
+            return Err(E::msg("Empty text batch"));
+        }
+
+        // Encode the batch with the tokenizer, padding every row to the longest in the batch
+        let mut tokenizer = self.tokenizer.clone();
+        tokenizer.with_truncation(Some(TruncationParams {
+            max_length: max_length.unwrap_or(512),
+            strategy: TruncationStrategy::LongestFirst,
+            stride: 0,
+            direction: TruncationDirection::Right,
+        })).map_err(E::msg)?;
+        tokenizer.with_padding(Some(PaddingParams {
+            strategy: PaddingStrategy::BatchLongest,
+            ..Default::default()
+        }));
+
+        let encodings = tokenizer.encode_batch(texts.to_vec(), true).map_err(E::msg)?;
+
+        // Stack the per-row token ids and attention masks into [batch, seq_len] tensors
+        let token_id_rows = encodings.iter()
+            .map(|encoding| Tensor::new(encoding.get_ids(), &self.device))
+            .collect::<candle_core::Result<Vec<_>>>()?;
+        let attention_mask_rows = encodings.iter()
+            .map(|encoding| Tensor::new(encoding.get_attention_mask(), &self.device))
+            .collect::<candle_core::Result<Vec<_>>>()?;
+
+        let token_ids = Tensor::stack(&token_id_rows, 0)?;
+        let attention_mask = Tensor::stack(&attention_mask_rows, 0)?;
+        let token_type_ids = token_ids.zeros_like()?;
+
+        // Run the whole batch through BERT in one forward call
+        let embeddings = self.model.forward(&token_ids, &token_type_ids, Some(&attention_mask))?;
+
+        let pooled = match self.pooling {
+            PoolingMode::Mean => {
+                // Mean pooling: zero out padded positions before summing, since BERT still
+                // emits non-zero outputs for [PAD] tokens, then divide by the real-token count.
+                let mask = attention_mask.to_dtype(embeddings.dtype())?.unsqueeze(2)?;
+                let masked_embeddings = embeddings.broadcast_mul(&mask)?;
+                let sum_embeddings = masked_embeddings.sum(1)?;
+                let attention_sum = attention_mask.sum(1)?.to_dtype(embeddings.dtype())?.unsqueeze(1)?;
+                sum_embeddings.broadcast_div(&attention_sum)?
+            }
+            PoolingMode::Cls => {
+                // CLS pooling: take the first token's embedding of every row
+                embeddings.narrow(1, 0, 1)?.squeeze(1)?
+            }
+        };
+
+        // Convert to float32, apply the Dense projection (if any), and normalize each row
+        let embedding = pooled.to_dtype(DType::F32)?;
+        let embedding = self.apply_dense(&embedding)?;
+
+        if self.normalize {
+            normalize_l2(&embedding)
+        } else {
+            Ok(embedding)
+        }
     }
 
     // Calculate cosine similarity between two texts
@@ -181,26 +410,73 @@ impl BertSimilarity {
         }
         
         let query_embedding = self.get_embedding(query_text, max_length)?;
-        
-        // Calculate similarity for each candidate individually
+        let candidate_embeddings = self.get_embeddings_batch(candidates, max_length)?;
+
+        // Calculate similarity to every candidate in a single matmul: query . candidates^T
+        let similarities = query_embedding.matmul(&candidate_embeddings.transpose(0, 1)?)?;
+        let scores = similarities.squeeze(0)?.to_vec1::<f32>()?;
+
         let mut best_idx = 0;
         let mut best_score = -1.0;
-        
-        for (idx, candidate) in candidates.iter().enumerate() {
-            let candidate_embedding = self.get_embedding(candidate, max_length)?;
-            
-            // Calculate similarity (dot product of normalized vectors = cosine similarity)
-            let sim = query_embedding.matmul(&candidate_embedding.transpose(0, 1)?)?;
-            let score = sim.squeeze(0)?.squeeze(0)?.to_scalar::<f32>()?;
-            
+
+        for (idx, &score) in scores.iter().enumerate() {
             if score > best_score {
                 best_score = score;
                 best_idx = idx;
             }
         }
-        
+
         Ok((best_idx, best_score))
     }
+
+    // Find most similar text from a list using Reciprocal Rank Fusion of semantic similarity
+    // and an externally-supplied lexical score (e.g. BM25), so the two don't need to be
+    // numerically comparable. Returns the best index, its fused score, and the fused score
+    // for every candidate so the caller can re-rank.
+    pub fn find_most_similar_hybrid(
+        &self,
+        query_text: &str,
+        candidates: &[&str],
+        lexical_scores: &[f32],
+        semantic_weight: f32,
+        max_length: Option<usize>,
+    ) -> Result<(usize, f32, Vec<f32>)> {
+        if candidates.is_empty() {
+            return Err(E::msg("Empty candidate list"));
+        }
+        if candidates.len() != lexical_scores.len() {
+            return Err(E::msg("candidates and lexical_scores must have the same length"));
+        }
+
+        let query_embedding = self.get_embedding(query_text, max_length)?;
+        let candidate_embeddings = self.get_embeddings_batch(candidates, max_length)?;
+
+        let similarities = query_embedding.matmul(&candidate_embeddings.transpose(0, 1)?)?;
+        let semantic_scores = similarities.squeeze(0)?.to_vec1::<f32>()?;
+
+        let semantic_ranks = rrf_ranks(&semantic_scores);
+        let lexical_ranks = rrf_ranks(lexical_scores);
+
+        let fused_scores: Vec<f32> = (0..candidates.len())
+            .map(|idx| {
+                let semantic_rrf = 1.0 / (RRF_K + semantic_ranks[idx] as f32);
+                let lexical_rrf = 1.0 / (RRF_K + lexical_ranks[idx] as f32);
+                semantic_weight * semantic_rrf + (1.0 - semantic_weight) * lexical_rrf
+            })
+            .collect();
+
+        let mut best_idx = 0;
+        let mut best_score = f32::MIN;
+
+        for (idx, &score) in fused_scores.iter().enumerate() {
+            if score > best_score {
+                best_score = score;
+                best_idx = idx;
+            }
+        }
+
+        Ok((best_idx, best_score, fused_scores))
+    }
 }
 
 /// Tokenize the input text and return a `TokenizationResult` containing token IDs and tokens.
@@ -223,39 +499,25 @@ pub extern "C" fn tokenize_text(text: *const c_char, max_length: i32) -> Tokeniz
         }
     };
 
-    let bert_opt = BERT_SIMILARITY.lock().unwrap();
-    let bert = match &*bert_opt {
-        Some(b) => b,
-        None => {
-            eprintln!("BERT model not initialized");
-            return TokenizationResult {
-                token_ids: std::ptr::null_mut(),
-                token_count: 0,
-                tokens: std::ptr::null_mut(),
-                error: true
-            };
-        }
-    };
-
     let max_length_opt = if max_length <= 0 { None } else { Some(max_length as usize) };
-    match bert.tokenize_text(text, max_length_opt) {
-        Ok((token_ids, tokens)) => {
+    match with_model(DEFAULT_MODEL_HANDLE, |bert| bert.tokenize_text(text, max_length_opt)) {
+        Some(Ok((token_ids, tokens))) => {
             let count = token_ids.len() as i32;
-            
+
             // Allocate memory for token IDs
             let ids_ptr = token_ids.as_ptr() as *mut i32;
-            
+
             // Allocate memory for tokens
             let c_tokens: Vec<*mut c_char> = tokens.iter()
                 .map(|s| CString::new(s.as_str()).unwrap().into_raw())
                 .collect();
-            
+
             let tokens_ptr = c_tokens.as_ptr() as *mut *mut c_char;
-            
+
             // Don't drop the vectors - Go will own the memory now
             std::mem::forget(token_ids);
             std::mem::forget(c_tokens);
-            
+
             TokenizationResult {
                 token_ids: ids_ptr,
                 token_count: count,
@@ -263,7 +525,7 @@ pub extern "C" fn tokenize_text(text: *const c_char, max_length: i32) -> Tokeniz
                 error: false
             }
         },
-        Err(e) => {
+        Some(Err(e)) => {
             eprintln!("Error tokenizing text: {}", e);
             TokenizationResult {
                 token_ids: std::ptr::null_mut(),
@@ -272,6 +534,15 @@ pub extern "C" fn tokenize_text(text: *const c_char, max_length: i32) -> Tokeniz
                 error: true
             }
         }
+        None => {
+            eprintln!("BERT model not initialized");
+            TokenizationResult {
+                token_ids: std::ptr::null_mut(),
+                token_count: 0,
+                tokens: std::ptr::null_mut(),
+                error: true
+            }
+        }
     }
 }
 
@@ -311,8 +582,7 @@ pub extern "C" fn init_similarity_model(model_id: *const c_char, use_cpu: bool)
 
     match BertSimilarity::new(model_id, use_cpu) {
         Ok(model) => {
-            let mut bert_opt = BERT_SIMILARITY.lock().unwrap();
-            *bert_opt = Some(model);
+            register_model(DEFAULT_MODEL_HANDLE, model);
             true
         }
         Err(e) => {
@@ -322,6 +592,133 @@ pub extern "C" fn init_similarity_model(model_id: *const c_char, use_cpu: bool)
     }
 }
 
+// Initialize a named BERT model instance, alongside any other models already loaded (called from Go)
+#[no_mangle]
+pub extern "C" fn init_named_model(handle: *const c_char, model_id: *const c_char, use_cpu: bool) -> bool {
+    let handle = unsafe {
+        match CStr::from_ptr(handle).to_str() {
+            Ok(s) => s,
+            Err(_) => return false,
+        }
+    };
+
+    let model_id = unsafe {
+        match CStr::from_ptr(model_id).to_str() {
+            Ok(s) => s,
+            Err(_) => return false,
+        }
+    };
+
+    match BertSimilarity::new(model_id, use_cpu) {
+        Ok(model) => {
+            register_model(handle, model);
+            true
+        }
+        Err(e) => {
+            eprintln!("Failed to initialize named BERT model '{}': {}", handle, e);
+            false
+        }
+    }
+}
+
+// Initialize the BERT model with explicit weight source, revision, and pooling (called from Go)
+#[no_mangle]
+pub extern "C" fn init_similarity_model_with_options(
+    model_id: *const c_char,
+    revision: *const c_char,
+    weight_source: i32, // 0 = auto, 1 = safetensors, 2 = pytorch
+    pooling: i32,        // 0 = mean, 1 = cls
+    normalize: bool,
+    use_cpu: bool,
+) -> bool {
+    let model_id = unsafe {
+        match CStr::from_ptr(model_id).to_str() {
+            Ok(s) => s,
+            Err(_) => return false,
+        }
+    };
+
+    let revision = unsafe {
+        if revision.is_null() {
+            None
+        } else {
+            match CStr::from_ptr(revision).to_str() {
+                Ok(s) if !s.is_empty() => Some(s.to_string()),
+                _ => None,
+            }
+        }
+    };
+
+    let weight_source = match weight_source {
+        1 => Some(WeightSource::Safetensors),
+        2 => Some(WeightSource::Pytorch),
+        _ => None,
+    };
+
+    let pooling = if pooling == 1 { PoolingMode::Cls } else { PoolingMode::Mean };
+
+    let options = EmbedderOptions {
+        model_id: model_id.to_string(),
+        revision,
+        weight_source,
+        adapter_id: None,
+        use_cpu,
+        normalize,
+        pooling,
+    };
+
+    match BertSimilarity::with_options(options) {
+        Ok(model) => {
+            register_model(DEFAULT_MODEL_HANDLE, model);
+            true
+        }
+        Err(e) => {
+            eprintln!("Failed to initialize BERT: {}", e);
+            false
+        }
+    }
+}
+
+// Initialize the BERT model with a LoRA adapter layered on top of the base encoder (called from Go)
+#[no_mangle]
+pub extern "C" fn init_similarity_model_with_lora(
+    model_id: *const c_char,
+    adapter_id: *const c_char,
+    use_cpu: bool,
+) -> bool {
+    let model_id = unsafe {
+        match CStr::from_ptr(model_id).to_str() {
+            Ok(s) => s,
+            Err(_) => return false,
+        }
+    };
+
+    let adapter_id = unsafe {
+        match CStr::from_ptr(adapter_id).to_str() {
+            Ok(s) => s,
+            Err(_) => return false,
+        }
+    };
+
+    let options = EmbedderOptions {
+        model_id: model_id.to_string(),
+        adapter_id: Some(adapter_id.to_string()),
+        use_cpu,
+        ..Default::default()
+    };
+
+    match BertSimilarity::with_options(options) {
+        Ok(model) => {
+            register_model(DEFAULT_MODEL_HANDLE, model);
+            true
+        }
+        Err(e) => {
+            eprintln!("Failed to initialize BERT with LoRA adapter: {}", e);
+            false
+        }
+    }
+}
+
 // Structure to hold similarity result
 #[repr(C)]
 pub struct SimilarityResult {
@@ -351,22 +748,84 @@ pub extern "C" fn get_text_embedding(text: *const c_char, max_length: i32) -> Em
         }
     };
 
-    let bert_opt = BERT_SIMILARITY.lock().unwrap();
-    let bert = match &*bert_opt {
-        Some(b) => b,
+    let max_length_opt = if max_length <= 0 { None } else { Some(max_length as usize) };
+    match with_model(DEFAULT_MODEL_HANDLE, |bert| bert.get_embedding(text, max_length_opt)) {
+        Some(Ok(embedding)) => {
+            match embedding.flatten_all() {
+                Ok(flat_embedding) => {
+                    match flat_embedding.to_vec1::<f32>() {
+                        Ok(vec) => {
+                            let length = vec.len() as i32;
+                            // Allocate memory that will be freed by Go
+                            let data = vec.as_ptr() as *mut f32;
+                            std::mem::forget(vec); // Don't drop the vector - Go will own the memory now
+                            EmbeddingResult {
+                                data,
+                                length,
+                                error: false
+                            }
+                        },
+                        Err(_) => EmbeddingResult {
+                            data: std::ptr::null_mut(),
+                            length: 0,
+                            error: true
+                        }
+                    }
+                },
+                Err(_) => EmbeddingResult {
+                    data: std::ptr::null_mut(),
+                    length: 0,
+                    error: true
+                }
+            }
+        },
+        Some(Err(e)) => {
+            eprintln!("Error getting embedding: {}", e);
+            EmbeddingResult {
+                data: std::ptr::null_mut(),
+                length: 0,
+                error: true
+            }
+        }
         None => {
             eprintln!("BERT model not initialized");
-            return EmbeddingResult {
+            EmbeddingResult {
                 data: std::ptr::null_mut(),
                 length: 0,
                 error: true
-            };
+            }
+        }
+    }
+}
+
+// Get embedding for a text from a named model instance (called from Go)
+#[no_mangle]
+pub extern "C" fn get_named_text_embedding(handle: *const c_char, text: *const c_char, max_length: i32) -> EmbeddingResult {
+    let handle = unsafe {
+        match CStr::from_ptr(handle).to_str() {
+            Ok(s) => s,
+            Err(_) => return EmbeddingResult {
+                data: std::ptr::null_mut(),
+                length: 0,
+                error: true
+            },
+        }
+    };
+
+    let text = unsafe {
+        match CStr::from_ptr(text).to_str() {
+            Ok(s) => s,
+            Err(_) => return EmbeddingResult {
+                data: std::ptr::null_mut(),
+                length: 0,
+                error: true
+            },
         }
     };
 
     let max_length_opt = if max_length <= 0 { None } else { Some(max_length as usize) };
-    match bert.get_embedding(text, max_length_opt) {
-        Ok(embedding) => {
+    match with_model(handle, |bert| bert.get_embedding(text, max_length_opt)) {
+        Some(Ok(embedding)) => {
             match embedding.flatten_all() {
                 Ok(flat_embedding) => {
                     match flat_embedding.to_vec1::<f32>() {
@@ -395,7 +854,7 @@ pub extern "C" fn get_text_embedding(text: *const c_char, max_length: i32) -> Em
                 }
             }
         },
-        Err(e) => {
+        Some(Err(e)) => {
             eprintln!("Error getting embedding: {}", e);
             EmbeddingResult {
                 data: std::ptr::null_mut(),
@@ -403,6 +862,104 @@ pub extern "C" fn get_text_embedding(text: *const c_char, max_length: i32) -> Em
                 error: true
             }
         }
+        None => {
+            eprintln!("Named BERT model '{}' not initialized", handle);
+            EmbeddingResult {
+                data: std::ptr::null_mut(),
+                length: 0,
+                error: true
+            }
+        }
+    }
+}
+
+// Structure to hold a batch of embeddings flattened into a single buffer (row-major, `cols` per row)
+#[repr(C)]
+pub struct EmbeddingBatchResult {
+    pub data: *mut f32,
+    pub rows: i32,
+    pub cols: i32,
+    pub error: bool,
+}
+
+// Get embeddings for a batch of texts in a single forward pass (called from Go)
+#[no_mangle]
+pub extern "C" fn get_text_embeddings_batch(
+    texts_ptr: *const *const c_char,
+    num_texts: i32,
+    max_length: i32
+) -> EmbeddingBatchResult {
+    let texts: Vec<&str> = unsafe {
+        let mut result = Vec::with_capacity(num_texts as usize);
+        let texts_slice = std::slice::from_raw_parts(texts_ptr, num_texts as usize);
+
+        for &cstr in texts_slice {
+            match CStr::from_ptr(cstr).to_str() {
+                Ok(s) => result.push(s),
+                Err(_) => return EmbeddingBatchResult {
+                    data: std::ptr::null_mut(),
+                    rows: 0,
+                    cols: 0,
+                    error: true
+                },
+            }
+        }
+
+        result
+    };
+
+    let max_length_opt = if max_length <= 0 { None } else { Some(max_length as usize) };
+    match with_model(DEFAULT_MODEL_HANDLE, |bert| bert.get_embeddings_batch(&texts, max_length_opt)) {
+        Some(Ok(embeddings)) => {
+            let dims = match embeddings.dims2() {
+                Ok(dims) => dims,
+                Err(_) => return EmbeddingBatchResult {
+                    data: std::ptr::null_mut(),
+                    rows: 0,
+                    cols: 0,
+                    error: true
+                },
+            };
+
+            match embeddings.flatten_all().and_then(|flat| flat.to_vec1::<f32>()) {
+                Ok(vec) => {
+                    let (rows, cols) = dims;
+                    // Allocate memory that will be freed by Go
+                    let data = vec.as_ptr() as *mut f32;
+                    std::mem::forget(vec); // Don't drop the vector - Go will own the memory now
+                    EmbeddingBatchResult {
+                        data,
+                        rows: rows as i32,
+                        cols: cols as i32,
+                        error: false
+                    }
+                },
+                Err(_) => EmbeddingBatchResult {
+                    data: std::ptr::null_mut(),
+                    rows: 0,
+                    cols: 0,
+                    error: true
+                }
+            }
+        },
+        Some(Err(e)) => {
+            eprintln!("Error getting batch embeddings: {}", e);
+            EmbeddingBatchResult {
+                data: std::ptr::null_mut(),
+                rows: 0,
+                cols: 0,
+                error: true
+            }
+        }
+        None => {
+            eprintln!("BERT model not initialized");
+            EmbeddingBatchResult {
+                data: std::ptr::null_mut(),
+                rows: 0,
+                cols: 0,
+                error: true
+            }
+        }
     }
 }
 
@@ -423,22 +980,55 @@ pub extern "C" fn calculate_similarity(text1: *const c_char, text2: *const c_cha
         }
     };
 
-    let bert_opt = BERT_SIMILARITY.lock().unwrap();
-    let bert = match &*bert_opt {
-        Some(b) => b,
+    let max_length_opt = if max_length <= 0 { None } else { Some(max_length as usize) };
+    match with_model(DEFAULT_MODEL_HANDLE, |bert| bert.calculate_similarity(text1, text2, max_length_opt)) {
+        Some(Ok(similarity)) => similarity,
+        Some(Err(e)) => {
+            eprintln!("Error calculating similarity: {}", e);
+            -1.0
+        }
         None => {
             eprintln!("BERT model not initialized");
-            return -1.0;
+            -1.0
+        }
+    }
+}
+
+// Calculate similarity between two texts using a named model instance (called from Go)
+#[no_mangle]
+pub extern "C" fn calculate_named_similarity(handle: *const c_char, text1: *const c_char, text2: *const c_char, max_length: i32) -> f32 {
+    let handle = unsafe {
+        match CStr::from_ptr(handle).to_str() {
+            Ok(s) => s,
+            Err(_) => return -1.0,
+        }
+    };
+
+    let text1 = unsafe {
+        match CStr::from_ptr(text1).to_str() {
+            Ok(s) => s,
+            Err(_) => return -1.0,
+        }
+    };
+
+    let text2 = unsafe {
+        match CStr::from_ptr(text2).to_str() {
+            Ok(s) => s,
+            Err(_) => return -1.0,
         }
     };
 
     let max_length_opt = if max_length <= 0 { None } else { Some(max_length as usize) };
-    match bert.calculate_similarity(text1, text2, max_length_opt) {
-        Ok(similarity) => similarity,
-        Err(e) => {
+    match with_model(handle, |bert| bert.calculate_similarity(text1, text2, max_length_opt)) {
+        Some(Ok(similarity)) => similarity,
+        Some(Err(e)) => {
             eprintln!("Error calculating similarity: {}", e);
             -1.0
         }
+        None => {
+            eprintln!("Named BERT model '{}' not initialized", handle);
+            -1.0
+        }
     }
 }
 
@@ -472,25 +1062,148 @@ pub extern "C" fn find_most_similar(
         result
     };
 
-    let bert_opt = BERT_SIMILARITY.lock().unwrap();
-    let bert = match &*bert_opt {
-        Some(b) => b,
+    let max_length_opt = if max_length <= 0 { None } else { Some(max_length as usize) };
+    match with_model(DEFAULT_MODEL_HANDLE, |bert| bert.find_most_similar(query, &candidates, max_length_opt)) {
+        Some(Ok((idx, score))) => SimilarityResult {
+            index: idx as i32,
+            score
+        },
+        Some(Err(e)) => {
+            eprintln!("Error finding most similar: {}", e);
+            SimilarityResult { index: -1, score: -1.0 }
+        }
         None => {
             eprintln!("BERT model not initialized");
-            return SimilarityResult { index: -1, score: -1.0 };
+            SimilarityResult { index: -1, score: -1.0 }
+        }
+    }
+}
+
+// Find most similar text from a list using a named model instance (called from Go)
+#[no_mangle]
+pub extern "C" fn find_named_most_similar(
+    handle: *const c_char,
+    query: *const c_char,
+    candidates_ptr: *const *const c_char,
+    num_candidates: i32,
+    max_length: i32
+) -> SimilarityResult {
+    let handle = unsafe {
+        match CStr::from_ptr(handle).to_str() {
+            Ok(s) => s,
+            Err(_) => return SimilarityResult { index: -1, score: -1.0 },
+        }
+    };
+
+    let query = unsafe {
+        match CStr::from_ptr(query).to_str() {
+            Ok(s) => s,
+            Err(_) => return SimilarityResult { index: -1, score: -1.0 },
+        }
+    };
+
+    // Convert the array of C strings to Rust strings
+    let candidates: Vec<&str> = unsafe {
+        let mut result = Vec::with_capacity(num_candidates as usize);
+        let candidates_slice = std::slice::from_raw_parts(candidates_ptr, num_candidates as usize);
+
+        for &cstr in candidates_slice {
+            match CStr::from_ptr(cstr).to_str() {
+                Ok(s) => result.push(s),
+                Err(_) => return SimilarityResult { index: -1, score: -1.0 },
+            }
         }
+
+        result
     };
 
     let max_length_opt = if max_length <= 0 { None } else { Some(max_length as usize) };
-    match bert.find_most_similar(query, &candidates, max_length_opt) {
-        Ok((idx, score)) => SimilarityResult { 
-            index: idx as i32, 
-            score 
+    match with_model(handle, |bert| bert.find_most_similar(query, &candidates, max_length_opt)) {
+        Some(Ok((idx, score))) => SimilarityResult {
+            index: idx as i32,
+            score
         },
-        Err(e) => {
+        Some(Err(e)) => {
             eprintln!("Error finding most similar: {}", e);
             SimilarityResult { index: -1, score: -1.0 }
         }
+        None => {
+            eprintln!("Named BERT model '{}' not initialized", handle);
+            SimilarityResult { index: -1, score: -1.0 }
+        }
+    }
+}
+
+// Structure to hold a hybrid (semantic + lexical) similarity result
+#[repr(C)]
+pub struct HybridSimilarityResult {
+    pub index: i32,        // Index of the best-fused candidate
+    pub score: f32,        // Fused score of the best candidate
+    pub fused_scores: *mut f32, // Fused score for every candidate, for re-ranking by the caller
+    pub fused_scores_len: i32,
+}
+
+// Find most similar text from a list via Reciprocal Rank Fusion of semantic similarity and an
+// externally-supplied lexical score, e.g. BM25 (called from Go)
+#[no_mangle]
+pub extern "C" fn find_most_similar_hybrid(
+    query: *const c_char,
+    candidates_ptr: *const *const c_char,
+    lexical_scores_ptr: *const f32,
+    num_candidates: i32,
+    semantic_weight: f32,
+    max_length: i32
+) -> HybridSimilarityResult {
+    let query = unsafe {
+        match CStr::from_ptr(query).to_str() {
+            Ok(s) => s,
+            Err(_) => return HybridSimilarityResult { index: -1, score: -1.0, fused_scores: std::ptr::null_mut(), fused_scores_len: 0 },
+        }
+    };
+
+    // Convert the array of C strings to Rust strings
+    let candidates: Vec<&str> = unsafe {
+        let mut result = Vec::with_capacity(num_candidates as usize);
+        let candidates_slice = std::slice::from_raw_parts(candidates_ptr, num_candidates as usize);
+
+        for &cstr in candidates_slice {
+            match CStr::from_ptr(cstr).to_str() {
+                Ok(s) => result.push(s),
+                Err(_) => return HybridSimilarityResult { index: -1, score: -1.0, fused_scores: std::ptr::null_mut(), fused_scores_len: 0 },
+            }
+        }
+
+        result
+    };
+
+    let lexical_scores: &[f32] = unsafe {
+        std::slice::from_raw_parts(lexical_scores_ptr, num_candidates as usize)
+    };
+
+    let max_length_opt = if max_length <= 0 { None } else { Some(max_length as usize) };
+    match with_model(DEFAULT_MODEL_HANDLE, |bert| {
+        bert.find_most_similar_hybrid(query, &candidates, lexical_scores, semantic_weight, max_length_opt)
+    }) {
+        Some(Ok((idx, score, fused_scores))) => {
+            let fused_scores_len = fused_scores.len() as i32;
+            // Allocate memory that will be freed by Go (via free_embedding)
+            let data = fused_scores.as_ptr() as *mut f32;
+            std::mem::forget(fused_scores);
+            HybridSimilarityResult {
+                index: idx as i32,
+                score,
+                fused_scores: data,
+                fused_scores_len,
+            }
+        }
+        Some(Err(e)) => {
+            eprintln!("Error finding most similar (hybrid): {}", e);
+            HybridSimilarityResult { index: -1, score: -1.0, fused_scores: std::ptr::null_mut(), fused_scores_len: 0 }
+        }
+        None => {
+            eprintln!("BERT model not initialized");
+            HybridSimilarityResult { index: -1, score: -1.0, fused_scores: std::ptr::null_mut(), fused_scores_len: 0 }
+        }
     }
 }
 
@@ -516,8 +1229,102 @@ pub extern "C" fn free_embedding(data: *mut f32, length: i32) {
     }
 }
 
+// Free batch embedding data allocated by Rust
+#[no_mangle]
+pub extern "C" fn free_embedding_batch(data: *mut f32, rows: i32, cols: i32) {
+    let length = (rows as usize) * (cols as usize);
+    if !data.is_null() && length > 0 {
+        unsafe {
+            // Reconstruct the vector so that Rust can properly deallocate it
+            let _vec = Vec::from_raw_parts(data, length, length);
+            // The vector will be dropped and the memory freed when _vec goes out of scope
+        }
+    }
+}
+
 // Helper function to L2 normalize a tensor
 fn normalize_l2(v: &Tensor) -> Result<Tensor> {
     let norm = v.sqr()?.sum_keepdim(1)?.sqrt()?;
     Ok(v.broadcast_div(&norm)?)
-} 
\ No newline at end of file
+}
+
+// Reciprocal Rank Fusion smoothing constant
+const RRF_K: f32 = 60.0;
+
+// Rank scores in descending order (1-based), for Reciprocal Rank Fusion
+fn rrf_ranks(scores: &[f32]) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..scores.len()).collect();
+    indices.sort_by(|&a, &b| scores[b].partial_cmp(&scores[a]).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut ranks = vec![0usize; scores.len()];
+    for (rank, &idx) in indices.iter().enumerate() {
+        ranks[idx] = rank + 1;
+    }
+    ranks
+}
+
+// `adapter_config.json` fields needed to scale a LoRA adapter (see the peft `LoraConfig` format)
+#[derive(serde::Deserialize)]
+struct LoraAdapterConfig {
+    r: usize,
+    lora_alpha: f64,
+}
+
+// Load every tensor in a weights file into a name-keyed map, regardless of format. Tensors are
+// moved onto `device` and converted to `DTYPE`, matching what `VarBuilder::from_pth`/
+// `from_mmaped_safetensors` do for the non-adapter path (`pickle::read_all` ignores `device`,
+// and both loaders otherwise keep the on-disk dtype).
+fn load_weights_map(path: &std::path::Path, use_pth: bool, device: &Device) -> Result<HashMap<String, Tensor>> {
+    let raw: HashMap<String, Tensor> = if use_pth {
+        candle_core::pickle::read_all(path)?.into_iter().collect()
+    } else {
+        candle_core::safetensors::load(path, device)?
+    };
+
+    raw.into_iter()
+        .map(|(name, tensor)| Ok((name, tensor.to_device(device)?.to_dtype(DTYPE)?)))
+        .collect()
+}
+
+// Fetch a LoRA adapter from HF and merge its low-rank A/B deltas into the matching base weights:
+// `weight += (alpha/r) * B @ A`. Mathematically identical to wrapping the query/value linears at
+// forward time, but doesn't require BertModel to expose its internal layers.
+fn merge_lora_adapter(weights: &mut HashMap<String, Tensor>, adapter_id: &str, device: &Device) -> Result<()> {
+    let api = Api::new()?;
+    let api = api.repo(Repo::model(adapter_id.to_string()));
+
+    let config_filename = api.get("adapter_config.json")?;
+    let config: LoraAdapterConfig = serde_json::from_str(&std::fs::read_to_string(config_filename)?)?;
+    let scaling = config.lora_alpha / config.r as f64;
+
+    let adapter_weights_filename = api.get("adapter_model.safetensors")?;
+    let adapter_tensors: HashMap<String, Tensor> = candle_core::safetensors::load(&adapter_weights_filename, device)?
+        .into_iter()
+        .map(|(name, tensor)| Ok((name, tensor.to_device(device)?.to_dtype(DTYPE)?)))
+        .collect::<Result<_>>()?;
+
+    const LORA_A_SUFFIX: &str = ".lora_A.weight";
+    for (name, a) in adapter_tensors.iter() {
+        let Some(prefix) = name.strip_suffix(LORA_A_SUFFIX) else {
+            continue;
+        };
+        let b = match adapter_tensors.get(&format!("{prefix}.lora_B.weight")) {
+            Some(b) => b,
+            None => continue,
+        };
+
+        // peft-style adapter names are prefixed relative to the base model
+        // ("base_model.model.<base weight path>"); match against the base weights by suffix.
+        let target_suffix = format!("{}.weight", prefix.trim_start_matches("base_model.model."));
+        let base_name = match weights.keys().find(|key| key.ends_with(&target_suffix)) {
+            Some(key) => key.clone(),
+            None => continue,
+        };
+
+        let delta = (b.matmul(a)? * scaling)?;
+        let merged = (weights[&base_name].clone() + delta)?;
+        weights.insert(base_name, merged);
+    }
+
+    Ok(())
+}
\ No newline at end of file